@@ -0,0 +1,157 @@
+//! Fusão de múltiplos provedores de localização (GPS, rede, passivo).
+//!
+//! `get_fix_from_provider(..., "gps")` sozinho depende exclusivamente do
+//! provedor `"gps"` e falha por completo quando ele está desabilitado,
+//! mesmo que o Android ofereça
+//! `"network"` (posicionamento por rede) e `"passive"` (última posição
+//! obtida por qualquer app) como alternativas. Este módulo consulta
+//! todos os provedores habilitados e escolhe o fix mais confiável,
+//! mantendo o app utilizável em ambientes fechados.
+
+use crate::gps::{attach_env, get_fix_from_provider, GpsFix};
+use log::warn;
+
+/// Provedor de localização que produziu um fix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LocationSource {
+    Gps,
+    Network,
+    Passive,
+}
+
+impl LocationSource {
+    fn provider_name(self) -> &'static str {
+        match self {
+            LocationSource::Gps => "gps",
+            LocationSource::Network => "network",
+            LocationSource::Passive => "passive",
+        }
+    }
+
+    /// Rótulo exibido ao usuário indicando a origem do fix mostrado.
+    pub fn label_pt(self) -> &'static str {
+        match self {
+            LocationSource::Gps => "(GPS)",
+            LocationSource::Network => "(rede)",
+            LocationSource::Passive => "(passivo)",
+        }
+    }
+}
+
+const PROVIDERS: [LocationSource; 3] =
+    [LocationSource::Gps, LocationSource::Network, LocationSource::Passive];
+
+/// Diferença mínima de idade, em milissegundos, para que um fix seja
+/// considerado "bem mais recente" que outro e preferido só por isso.
+const FRESHNESS_THRESHOLD_MS: i64 = 30_000;
+
+/// Um fix escolhido pela fusão, junto com o provedor que o produziu.
+#[derive(Debug, Clone, Copy)]
+pub struct FusedFix {
+    pub fix: GpsFix,
+    pub source: LocationSource,
+}
+
+/// Consulta todos os provedores habilitados e escolhe o fix mais
+/// confiável: prefere um fix mais novo por mais de
+/// [`FRESHNESS_THRESHOLD_MS`], caso contrário prefere o de menor raio de
+/// precisão (`accuracy`).
+pub fn get_best_fix() -> Result<FusedFix, Box<dyn std::error::Error>> {
+    let (vm, context) = attach_env()?;
+    let mut env = vm.attach_current_thread()?;
+
+    let mut best: Option<FusedFix> = None;
+
+    for source in PROVIDERS {
+        let fix = match get_fix_from_provider(&mut env, &context, source.provider_name()) {
+            Ok(Some(fix)) => fix,
+            Ok(None) => continue,
+            Err(e) => {
+                warn!("[Fusion] Provedor {:?} indisponível: {}", source, e);
+                continue;
+            }
+        };
+
+        best = Some(match best {
+            None => FusedFix { fix, source },
+            Some(current) if is_better(&fix, &current.fix) => FusedFix { fix, source },
+            Some(current) => current,
+        });
+    }
+
+    best.ok_or_else(|| "Nenhum provedor de localização retornou um fix".into())
+}
+
+/// Decide se `candidate` deve substituir `current` como melhor fix.
+fn is_better(candidate: &GpsFix, current: &GpsFix) -> bool {
+    let age_diff_ms = candidate.timestamp_ms - current.timestamp_ms;
+
+    if age_diff_ms > FRESHNESS_THRESHOLD_MS {
+        return true;
+    }
+    if age_diff_ms < -FRESHNESS_THRESHOLD_MS {
+        return false;
+    }
+
+    match (candidate.accuracy, current.accuracy) {
+        (Some(a), Some(b)) => a < b,
+        (Some(_), None) => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fix(timestamp_ms: i64, accuracy: Option<f64>) -> GpsFix {
+        GpsFix {
+            latitude: 0.0,
+            longitude: 0.0,
+            altitude: None,
+            speed: None,
+            bearing: None,
+            accuracy,
+            timestamp_ms,
+        }
+    }
+
+    #[test]
+    fn prefers_candidate_much_newer_regardless_of_accuracy() {
+        let current = fix(0, Some(5.0));
+        let candidate = fix(FRESHNESS_THRESHOLD_MS + 1, Some(100.0));
+        assert!(is_better(&candidate, &current));
+    }
+
+    #[test]
+    fn rejects_candidate_much_older_regardless_of_accuracy() {
+        let current = fix(FRESHNESS_THRESHOLD_MS + 1, Some(100.0));
+        let candidate = fix(0, Some(5.0));
+        assert!(!is_better(&candidate, &current));
+    }
+
+    #[test]
+    fn prefers_more_accurate_fix_when_age_is_comparable() {
+        let current = fix(0, Some(20.0));
+        let candidate = fix(1_000, Some(5.0));
+        assert!(is_better(&candidate, &current));
+
+        let current = fix(0, Some(5.0));
+        let candidate = fix(1_000, Some(20.0));
+        assert!(!is_better(&candidate, &current));
+    }
+
+    #[test]
+    fn prefers_fix_with_known_accuracy_over_unknown_when_age_is_comparable() {
+        let current = fix(0, None);
+        let candidate = fix(0, Some(50.0));
+        assert!(is_better(&candidate, &current));
+    }
+
+    #[test]
+    fn keeps_current_when_neither_has_known_accuracy_and_age_is_comparable() {
+        let current = fix(0, None);
+        let candidate = fix(0, None);
+        assert!(!is_better(&candidate, &current));
+    }
+}
@@ -0,0 +1,206 @@
+//! Acesso de baixo nível ao `LocationManager` do Android via JNI.
+
+use jni::objects::{JObject, JValue};
+use jni::JavaVM;
+use ndk_context::android_context;
+use std::os::raw::c_void;
+
+/// Devolve o `JavaVM` e o `Context` da Activity obtidos do `ndk_context`.
+/// Não anexa a thread atual à JVM: quem chama obtém o `JNIEnv` com
+/// `attach_current_thread` sobre o `JavaVM` devolvido.
+///
+/// Usado por todo o módulo de GPS para evitar repetir a obtenção do
+/// `JavaVM`/`Context` a partir do `ndk_context` em cada chamada JNI.
+pub(crate) fn attach_env<'a>() -> Result<(JavaVM, JObject<'a>), Box<dyn std::error::Error>> {
+    let ctx = android_context();
+    let vm_ptr = ctx.vm() as *mut jni::sys::JavaVM;
+    let env_ptr = ctx.context() as *mut c_void;
+
+    if vm_ptr.is_null() || env_ptr.is_null() {
+        return Err("Contexto Android não disponível".into());
+    }
+
+    let vm = unsafe { JavaVM::from_raw(vm_ptr)? };
+    let context = unsafe { JObject::from_raw(env_ptr as jni::sys::jobject) };
+
+    Ok((vm, context))
+}
+
+/// Obtém o `LocationManager` do sistema a partir do `Context` da Activity.
+pub(crate) fn location_manager<'a>(
+    env: &mut jni::JNIEnv<'a>,
+    context: &JObject<'a>,
+) -> Result<JObject<'a>, Box<dyn std::error::Error>> {
+    let service_str = env.new_string("location")?;
+    let location_service = env
+        .call_method(
+            context,
+            "getSystemService",
+            "(Ljava/lang/String;)Ljava/lang/Object;",
+            &[JValue::Object(&JObject::from(service_str))],
+        )?
+        .l()?;
+
+    if location_service.is_null() {
+        return Err("Serviço de localização não disponível".into());
+    }
+
+    Ok(location_service)
+}
+
+fn has_permission<'a>(
+    env: &mut jni::JNIEnv<'a>,
+    context: &JObject<'a>,
+    permission: &str,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let permission_str = env.new_string(permission)?;
+    let result = env
+        .call_method(
+            context,
+            "checkSelfPermission",
+            "(Ljava/lang/String;)I",
+            &[JValue::Object(&JObject::from(permission_str))],
+        )?
+        .i()?;
+
+    Ok(result == 0) // PERMISSION_GRANTED = 0
+}
+
+/// Verifica se `android.permission.ACCESS_FINE_LOCATION` já foi
+/// concedida. Exigida pelo provedor `"gps"`.
+pub(crate) fn has_fine_location_permission<'a>(
+    env: &mut jni::JNIEnv<'a>,
+    context: &JObject<'a>,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    has_permission(env, context, "android.permission.ACCESS_FINE_LOCATION")
+}
+
+/// Verifica se `android.permission.ACCESS_COARSE_LOCATION` já foi
+/// concedida. Suficiente para os provedores `"network"` e `"passive"`.
+pub(crate) fn has_coarse_location_permission<'a>(
+    env: &mut jni::JNIEnv<'a>,
+    context: &JObject<'a>,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    has_permission(env, context, "android.permission.ACCESS_COARSE_LOCATION")
+}
+
+/// Verifica se ao menos uma das permissões de localização (fina ou
+/// aproximada) foi concedida. É o suficiente para que a fusão de
+/// provedores (`network`/`passive`) funcione, mesmo sem
+/// `ACCESS_FINE_LOCATION`.
+pub(crate) fn has_any_location_permission<'a>(
+    env: &mut jni::JNIEnv<'a>,
+    context: &JObject<'a>,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    Ok(has_fine_location_permission(env, context)? || has_coarse_location_permission(env, context)?)
+}
+
+/// Um fix de localização completo, espelhando os campos do `Location`
+/// do Android (e, por baixo, do `GpsLocation` da HAL de GPS).
+///
+/// `altitude`, `speed`, `bearing` e `accuracy` são opcionais porque o
+/// `Location` só os preenche quando o provedor os reporta — cada um é
+/// lido a partir do respectivo `has*`/`get*` (`hasAltitude`/`getAltitude`,
+/// etc.) antes de ser exposto.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GpsFix {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub altitude: Option<f64>,
+    pub speed: Option<f64>,
+    pub bearing: Option<f64>,
+    pub accuracy: Option<f64>,
+    /// Milissegundos desde a época UTC, de `Location.getTime()`.
+    pub timestamp_ms: i64,
+}
+
+/// Lê um `GpsFix` a partir de um `android.location.Location` já obtido
+/// via JNI, consultando cada campo opcional pelo seu `has*` antes do
+/// `get*` correspondente.
+pub(crate) fn fix_from_location<'a>(
+    env: &mut jni::JNIEnv<'a>,
+    location: &JObject<'a>,
+) -> Result<GpsFix, Box<dyn std::error::Error>> {
+    let latitude = env.call_method(location, "getLatitude", "()D", &[])?.d()?;
+    let longitude = env.call_method(location, "getLongitude", "()D", &[])?.d()?;
+
+    let altitude = if env.call_method(location, "hasAltitude", "()Z", &[])?.z()? {
+        Some(env.call_method(location, "getAltitude", "()D", &[])?.d()?)
+    } else {
+        None
+    };
+
+    let speed = if env.call_method(location, "hasSpeed", "()Z", &[])?.z()? {
+        Some(env.call_method(location, "getSpeed", "()F", &[])?.f()? as f64)
+    } else {
+        None
+    };
+
+    let bearing = if env.call_method(location, "hasBearing", "()Z", &[])?.z()? {
+        Some(env.call_method(location, "getBearing", "()F", &[])?.f()? as f64)
+    } else {
+        None
+    };
+
+    let accuracy = if env.call_method(location, "hasAccuracy", "()Z", &[])?.z()? {
+        Some(env.call_method(location, "getAccuracy", "()F", &[])?.f()? as f64)
+    } else {
+        None
+    };
+
+    let timestamp_ms = env.call_method(location, "getTime", "()J", &[])?.j()?;
+
+    Ok(GpsFix {
+        latitude,
+        longitude,
+        altitude,
+        speed,
+        bearing,
+        accuracy,
+        timestamp_ms,
+    })
+}
+
+/// Busca a última localização conhecida de um provedor específico
+/// (`"gps"`, `"network"`, `"passive"`, ...).
+///
+/// Retorna `Ok(None)` quando o provedor está desabilitado ou ainda não
+/// tem nenhuma localização conhecida — isso não é um erro, apenas a
+/// ausência de um fix utilizável vindo desse provedor em particular.
+pub(crate) fn get_fix_from_provider<'a>(
+    env: &mut jni::JNIEnv<'a>,
+    context: &JObject<'a>,
+    provider: &str,
+) -> Result<Option<GpsFix>, Box<dyn std::error::Error>> {
+    let location_manager = location_manager(env, context)?;
+    let provider_str = env.new_string(provider)?;
+
+    let is_enabled = env
+        .call_method(
+            &location_manager,
+            "isProviderEnabled",
+            "(Ljava/lang/String;)Z",
+            &[JValue::Object(&JObject::from(provider_str))],
+        )?
+        .z()?;
+
+    if !is_enabled {
+        return Ok(None);
+    }
+
+    let provider_str = env.new_string(provider)?;
+    let location_obj = env
+        .call_method(
+            &location_manager,
+            "getLastKnownLocation",
+            "(Ljava/lang/String;)Landroid/location/Location;",
+            &[JValue::Object(&JObject::from(provider_str))],
+        )?
+        .l()?;
+
+    if location_obj.is_null() {
+        return Ok(None);
+    }
+
+    Ok(Some(fix_from_location(env, &location_obj)?))
+}
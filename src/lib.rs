@@ -1,29 +1,112 @@
 #![no_main]
 
-use jni::objects::{JObject, JValue};
-use jni::JavaVM;
 use log::{info, LevelFilter};
-use ndk_context::android_context;
-use std::os::raw::c_void;
 use log::warn;
 use log::error;
 
+mod fusion;
+mod gps;
+mod listener;
+#[cfg(feature = "mock-location")]
+mod mock;
+mod nmea;
+mod permission;
+mod satellite;
+
 // Importação do Slint para Android
 #[cfg(target_os = "android")]
 slint::slint! {
     import { VerticalBox, LineEdit } from "std-widgets.slint";
     export component MainWindow inherits Window {
         in property <string> gps-text: "Aguardando dados de GPS...";
-        
+        in property <string> gps-altitude: "Altitude: --";
+        in property <string> gps-speed: "Velocidade: --";
+        in property <string> gps-bearing: "Direção: --";
+        in property <string> gps-accuracy: "Precisão: --";
+        in property <string> gps-satellites: "Satélites: --";
+
         VerticalBox {
             LineEdit {
                 text: gps-text;
                 read-only: true;
             }
+            LineEdit {
+                text: gps-altitude;
+                read-only: true;
+            }
+            LineEdit {
+                text: gps-speed;
+                read-only: true;
+            }
+            LineEdit {
+                text: gps-bearing;
+                read-only: true;
+            }
+            LineEdit {
+                text: gps-accuracy;
+                read-only: true;
+            }
+            LineEdit {
+                text: gps-satellites;
+                read-only: true;
+            }
         }
     }
 }
 
+/// Formata um campo opcional do fix (altitude, velocidade, ...) para
+/// exibição, usando "N/D" quando o provedor não reportou o valor.
+fn format_optional(value: Option<f64>, unit: &str) -> String {
+    match value {
+        Some(v) => format!("{:.1}{}", v, unit),
+        None => "N/D".to_string(),
+    }
+}
+
+/// Aplica um `GpsFix` às propriedades exibidas pela `MainWindow`.
+/// Compartilhado entre o stream contínuo e qualquer outra fonte que
+/// venha a produzir fixes (ex.: fusão de provedores, replay de mock).
+pub(crate) fn apply_fix_to_window(window: &MainWindow, fix: &gps::GpsFix) {
+    let text = format!("Latitude: {:.6}, Longitude: {:.6}", fix.latitude, fix.longitude);
+    window.set_gps_text(text.into());
+    window.set_gps_altitude(format!("Altitude: {}", format_optional(fix.altitude, " m")).into());
+    window.set_gps_speed(format!("Velocidade: {}", format_optional(fix.speed, " m/s")).into());
+    window.set_gps_bearing(format!("Direção: {}", format_optional(fix.bearing, "°")).into());
+    window.set_gps_accuracy(format!("Precisão: {}", format_optional(fix.accuracy, " m")).into());
+}
+
+/// Aplica um `FusedFix` às propriedades da `MainWindow`, anexando ao
+/// texto de coordenadas a origem do fix (`(GPS)` vs. `(rede)`) para que
+/// o usuário saiba de onde veio a posição mostrada.
+pub(crate) fn apply_fused_fix_to_window(window: &MainWindow, fused: &fusion::FusedFix) {
+    apply_fix_to_window(window, &fused.fix);
+    let text = format!(
+        "Latitude: {:.6}, Longitude: {:.6} {}",
+        fused.fix.latitude,
+        fused.fix.longitude,
+        fused.source.label_pt()
+    );
+    window.set_gps_text(text.into());
+}
+
+/// Aplica um `SatelliteStatus` à propriedade `gps-satellites` da
+/// `MainWindow`, mostrando em vista vs. usados no fix e o C/N0 médio.
+pub(crate) fn apply_satellite_status_to_window(
+    window: &MainWindow,
+    status: &satellite::SatelliteStatus,
+) {
+    let text = match status.average_cn0_dbhz() {
+        Some(avg_cn0) => format!(
+            "Satélites: {} em vista, {} usados (C/N0 médio: {:.1} dB-Hz)",
+            status.in_view(),
+            status.used_in_fix(),
+            avg_cn0
+        ),
+        None => "Satélites: 0 em vista".to_string(),
+    };
+    window.set_gps_satellites(text.into());
+}
+
 #[unsafe(no_mangle)]
 fn android_main(app: slint::android::AndroidApp) {
     // Inicialização do logger
@@ -38,128 +121,111 @@ fn android_main(app: slint::android::AndroidApp) {
 
     let window = MainWindow::new().unwrap();
 
-    // Atualização assíncrona das coordenadas GPS
+    // Atualização contínua das coordenadas GPS via LocationListener
     let window_weak = window.as_weak();
 
+    #[cfg(feature = "mock-location")]
+    if mock::is_enabled() {
+        info!("[GPS Thread] GPS_MOCK_TRACK ativo, usando replay mock em vez do LocationManager");
+        mock::start_mock_updates(window_weak, 1000, 20);
+        window.run().unwrap();
+        return;
+    }
+
     std::thread::spawn(move || {
-        info!("[GPS Thread] Thread de GPS iniciada");
-        
-        match get_gps_coordinates() {
-            Ok((lat, lon)) => {
-                info!("[GPS Thread] Coordenadas obtidas com sucesso: lat={:.6}, lon={:.6}", lat, lon);
-                let text = format!("Latitude: {:.4}, Longitude: {:.4}", lat, lon);
-                
-                if let Some(window) = window_weak.upgrade() {
-                    info!("[GPS Thread] Atualizando UI com as coordenadas");
-                    window.set_gps_text(text.into());
-                } else {
-                    warn!("[GPS Thread] Window já foi destruída, não é possível atualizar UI");
-                }
+        info!("[GPS Thread] Verificando permissão de localização");
+
+        let waiting_weak = window_weak.clone();
+        let permission_result = permission::request_location_permission(move || {
+            let _ = waiting_weak.upgrade_in_event_loop(|window| {
+                window.set_gps_text("Solicitando permissão de localização...".into());
+            });
+        });
+
+        if let Err(e) = permission_result {
+            error!("[GPS Thread] Permissão de localização negada: {}", e);
+            let error_text = format!("Erro: {}", e);
+            let _ = window_weak.upgrade_in_event_loop(move |window| {
+                window.set_gps_text(error_text.into());
+            });
+            return;
+        }
+
+        // Mostra um fix inicial imediatamente, usando o melhor provedor
+        // disponível (rede ou passivo podem responder antes do GPS dar o
+        // primeiro fix), enquanto o stream contínuo do GPS é registrado.
+        match fusion::get_best_fix() {
+            Ok(fused) => {
+                info!("[GPS Thread] Fix inicial via {:?}", fused.source);
+                let _ = window_weak.upgrade_in_event_loop(move |window| {
+                    apply_fused_fix_to_window(&window, &fused);
+                });
             }
             Err(e) => {
-                error!("[GPS Thread] Erro ao obter coordenadas: {}", e);
-                let error_text = format!("Erro: {}", e);
-                
-                if let Some(window) = window_weak.upgrade() {
-                    warn!("[GPS Thread] Atualizando UI com mensagem de erro");
-                    window.set_gps_text(error_text.into());
-                } else {
-                    warn!("[GPS Thread] Window já foi destruída, não é possível mostrar erro");
-                }
+                warn!("[GPS Thread] Nenhum fix inicial disponível: {}", e);
             }
         }
-        
-        info!("[GPS Thread] Finalizando thread de GPS");
-    });
-
-    window.run().unwrap();
-}
-
-fn get_gps_coordinates() -> Result<(f64, f64), Box<dyn std::error::Error>> {
-    let ctx = android_context();
-    let vm_ptr = ctx.vm() as *mut jni::sys::JavaVM;
-    let env_ptr = ctx.context() as *mut c_void;
-
-    // Verificação de ponteiros nulos
-    if vm_ptr.is_null() || env_ptr.is_null() {
-        return Err("Contexto Android não disponível".into());
-    }
-
-    let vm = unsafe { JavaVM::from_raw(vm_ptr)? };
-    let mut env = vm.attach_current_thread()?;
-
-    let context = unsafe { JObject::from_raw(env_ptr as jni::sys::jobject) };
-
-    // Verificação de permissões
-    let permission_str = env.new_string("android.permission.ACCESS_FINE_LOCATION")?;
-    let has_permission = env
-        .call_method(
-            &context,
-            "checkSelfPermission",
-            "(Ljava/lang/String;)I",
-            &[JValue::Object(&JObject::from(permission_str))],
-        )?
-        .i()?;
-
-    if has_permission != 0 { // PERMISSION_GRANTED = 0
-        return Err("Permissão de localização não concedida".into());
-    }
-
-    // Obtenção do serviço de localização
-    let service_str = env.new_string("location")?;
-    let location_service = env
-        .call_method(
-            &context,
-            "getSystemService",
-            "(Ljava/lang/String;)Ljava/lang/Object;",
-            &[JValue::Object(&JObject::from(service_str))],
-        )?
-        .l()?;
-
-    if location_service.is_null() {
-        return Err("Serviço de localização não disponível".into());
-    }
-
-    let location_manager = JObject::from(location_service);
-    let provider_str = env.new_string("gps")?;
-
-    // Verificação se o provedor GPS está habilitado
-    let is_enabled = env
-        .call_method(
-            &location_manager,
-            "isProviderEnabled",
-            "(Ljava/lang/String;)Z",
-            &[JValue::Object(&JObject::from(provider_str))],
-        )?
-        .z()?;
-
-    if !is_enabled {
-        return Err("Provedor GPS está desativado".into());
-    }
 
-    // Obtenção da última localização conhecida
-    let gps_str = env.new_string("gps")?;
-    let location_obj = env
-        .call_method(
-            &location_manager,
-            "getLastKnownLocation",
-            "(Ljava/lang/String;)Landroid/location/Location;",
-            &[JValue::Object(&JObject::from(gps_str))],
-        )?
-        .l()?;
-
-    if location_obj.is_null() {
-        return Err("Nenhuma localização conhecida disponível".into());
-    }
+        // O stream contínuo usa exclusivamente o provedor "gps" e exige
+        // ACCESS_FINE_LOCATION; com apenas ACCESS_COARSE_LOCATION (estado
+        // que `request_location_permission` já aceita, para viabilizar a
+        // fusão de provedores) o registro falharia com SecurityException.
+        // Pula o registro nesse caso em vez de deixar a chamada falhar,
+        // seguindo o mesmo padrão de "pular provedor indisponível" usado
+        // em `fusion::get_best_fix`.
+        let has_fine_permission = gps::attach_env()
+            .and_then(|(vm, context)| {
+                let mut env = vm.attach_current_thread()?;
+                Ok(gps::has_fine_location_permission(&mut env, &context)?)
+            })
+            .unwrap_or(false);
+
+        if !has_fine_permission {
+            warn!("[GPS Thread] Sem ACCESS_FINE_LOCATION, pulando registro do stream \"gps\"");
+        } else {
+            info!("[GPS Thread] Registrando stream contínuo de localização");
+
+            match listener::start_location_updates(window_weak.clone(), 2000, 0.0) {
+                Ok(updates) => {
+                    info!("[GPS Thread] Stream de localização registrado com sucesso");
+                    // O subsistema mantém o listener vivo por toda a vida do
+                    // processo; não há um ponto natural de `stop()` enquanto o
+                    // app estiver em primeiro plano.
+                    std::mem::forget(updates);
+                }
+                Err(e) => {
+                    // Não sobrescreve `gps-text`: um fix inicial válido (ex.
+                    // via fusão "rede"/"passivo") pode já estar exibido, e
+                    // essa falha de registro em segundo plano não o invalida.
+                    warn!("[GPS Thread] Erro ao registrar stream de localização: {}", e);
+                }
+            }
+        }
 
-    // Extração das coordenadas
-    let latitude = env
-        .call_method(&location_obj, "getLatitude", "()D", &[])?
-        .d()?;
+        match satellite::start_satellite_updates(window_weak.clone()) {
+            Ok(updates) => {
+                info!("[GNSS Status] Registro de status de satélites iniciado");
+                std::mem::forget(updates);
+            }
+            Err(e) => {
+                warn!("[GNSS Status] Não foi possível registrar status de satélites: {}", e);
+            }
+        }
 
-    let longitude = env
-        .call_method(&location_obj, "getLongitude", "()D", &[])?
-        .d()?;
+        let nmea_result = nmea::subscribe_nmea(
+            |sentence| info!("[NMEA] Sentença recebida: {:?}", sentence),
+            None,
+        );
+        match nmea_result {
+            Ok(updates) => {
+                info!("[NMEA] Captura de sentenças NMEA iniciada");
+                std::mem::forget(updates);
+            }
+            Err(e) => {
+                warn!("[NMEA] Não foi possível iniciar a captura de sentenças NMEA: {}", e);
+            }
+        }
+    });
 
-    Ok((latitude, longitude))
+    window.run().unwrap();
 }
\ No newline at end of file
@@ -0,0 +1,169 @@
+//! Stream contínuo de localizações via `LocationManager.requestLocationUpdates`.
+//!
+//! `gps::get_fix_from_provider` só entrega a última localização
+//! conhecida, que costuma estar desatualizada ou ausente. Este módulo
+//! registra um `LocationListener` de verdade junto ao Android e entrega
+//! cada fix recebido por um canal `crossbeam`, permitindo que a UI seja
+//! atualizada continuamente enquanto o dispositivo se move.
+
+use crate::gps::{attach_env, fix_from_location, location_manager, GpsFix};
+use crossbeam::channel::{unbounded, Receiver, Sender};
+use jni::objects::{GlobalRef, JObject, JValue};
+use jni::{JNIEnv, JavaVM, NativeMethod};
+use log::{error, info, warn};
+use std::os::raw::c_void;
+use std::sync::Mutex;
+
+use crate::MainWindow;
+
+/// Canal usado pelo callback nativo (chamado pela JVM em sua própria
+/// thread) para entregar atualizações à thread que as consome. Só
+/// suporta um listener ativo por vez (ver checagem em
+/// `start_location_updates`), já que o método nativo registrado via
+/// `RegisterNatives` não carrega um identificador de instância.
+static UPDATE_SENDER: Mutex<Option<Sender<GpsFix>>> = Mutex::new(None);
+
+/// Alça do subsistema de atualizações contínuas. Mantém a referência
+/// global do listener Java viva e permite encerrar o stream chamando
+/// `removeUpdates`, evitando vazar o listener e a thread associada.
+pub struct LocationUpdates {
+    vm: JavaVM,
+    listener: GlobalRef,
+}
+
+impl LocationUpdates {
+    /// Remove o listener do `LocationManager` e libera a referência global.
+    pub fn stop(self) -> Result<(), Box<dyn std::error::Error>> {
+        let mut env = self.vm.attach_current_thread()?;
+        let (_, context) = attach_env()?;
+        let location_manager = location_manager(&mut env, &context)?;
+        env.call_method(
+            &location_manager,
+            "removeUpdates",
+            "(Landroid/location/LocationListener;)V",
+            &[JValue::Object(self.listener.as_obj())],
+        )?;
+        *UPDATE_SENDER.lock().unwrap() = None;
+        Ok(())
+    }
+}
+
+/// Registra `nativeOnLocationChanged` na classe Java auxiliar
+/// `RustLocationListener` (ver
+/// `android/app/src/main/java/com/gpsservice/RustLocationListener.java`).
+fn register_natives(env: &mut JNIEnv) -> Result<(), Box<dyn std::error::Error>> {
+    let class = env.find_class("com/gpsservice/RustLocationListener")?;
+    let method = NativeMethod {
+        name: "nativeOnLocationChanged".into(),
+        sig: "(Landroid/location/Location;)V".into(),
+        fn_ptr: native_on_location_changed as *mut c_void,
+    };
+    env.register_native_methods(&class, &[method])?;
+    Ok(())
+}
+
+/// Callback nativo invocado pela JVM a cada `onLocationChanged`. Extrai o
+/// `GpsFix` completo e empurra para o canal; todo o trabalho de atualizar
+/// a UI acontece na thread consumidora, fora do contexto da JVM.
+extern "system" fn native_on_location_changed<'local>(
+    mut env: JNIEnv<'local>,
+    _listener: JObject<'local>,
+    location: JObject<'local>,
+) {
+    let result: Result<(), Box<dyn std::error::Error>> = (|| {
+        let fix = fix_from_location(&mut env, &location)?;
+
+        if let Some(sender) = UPDATE_SENDER.lock().unwrap().as_ref() {
+            sender.send(fix)?;
+        }
+        Ok(())
+    })();
+
+    if let Err(e) = result {
+        error!("[GPS Listener] Falha ao processar onLocationChanged: {}", e);
+    }
+}
+
+/// Inicia o stream contínuo de localizações e atualiza `window_weak` a
+/// cada fix recebido.
+///
+/// `interval_ms` e `min_distance_m` são repassados diretamente para
+/// `requestLocationUpdates` (tempo mínimo entre atualizações e distância
+/// mínima percorrida, respectivamente). O listener é registrado em uma
+/// thread com looper (`Looper.getMainLooper()`), conforme exigido pela
+/// API do Android.
+pub fn start_location_updates(
+    window_weak: slint::Weak<MainWindow>,
+    interval_ms: i64,
+    min_distance_m: f32,
+) -> Result<LocationUpdates, Box<dyn std::error::Error>> {
+    // `UPDATE_SENDER` é uma única célula global compartilhada por todo o
+    // processo; uma segunda chamada sobrescreveria o `Sender` da primeira
+    // sem avisar, deixando o primeiro listener (cuja `GlobalRef` e
+    // `removeUpdates` continuam vivos em seu próprio `LocationUpdates`)
+    // entregando fixes para um canal que ninguém mais lê. Rejeita a
+    // segunda chamada em vez de mascará-la.
+    if UPDATE_SENDER.lock().unwrap().is_some() {
+        return Err("Stream de localização já registrado; chame `stop()` antes de registrar outro".into());
+    }
+
+    let (vm, context) = attach_env()?;
+    let mut env = vm.attach_current_thread()?;
+
+    register_natives(&mut env)?;
+
+    let location_manager = location_manager(&mut env, &context)?;
+
+    let listener_class = env.find_class("com/gpsservice/RustLocationListener")?;
+    let listener_local = env.new_object(&listener_class, "()V", &[])?;
+    let listener: GlobalRef = env.new_global_ref(listener_local)?;
+
+    let looper = env
+        .call_static_method(
+            "android/os/Looper",
+            "getMainLooper",
+            "()Landroid/os/Looper;",
+            &[],
+        )?
+        .l()?;
+
+    let provider_str = env.new_string("gps")?;
+    env.call_method(
+        &location_manager,
+        "requestLocationUpdates",
+        "(Ljava/lang/String;JFLandroid/location/LocationListener;Landroid/os/Looper;)V",
+        &[
+            JValue::Object(&JObject::from(provider_str)),
+            JValue::Long(interval_ms),
+            JValue::Float(min_distance_m),
+            JValue::Object(listener.as_obj()),
+            JValue::Object(&looper),
+        ],
+    )?;
+
+    let (sender, receiver): (Sender<GpsFix>, Receiver<GpsFix>) = unbounded();
+    *UPDATE_SENDER.lock().unwrap() = Some(sender);
+
+    std::thread::spawn(move || {
+        info!("[GPS Listener] Aguardando atualizações de localização...");
+        for fix in receiver.iter() {
+            info!(
+                "[GPS Listener] Fix recebido: lat={:.6}, lon={:.6}",
+                fix.latitude, fix.longitude
+            );
+
+            // `Weak::upgrade` só é válido na thread do event loop; como
+            // esta thread é um worker dedicado ao GPS, a atualização da
+            // UI precisa ser agendada via `upgrade_in_event_loop`.
+            let result = window_weak.upgrade_in_event_loop(move |window| {
+                crate::apply_fix_to_window(&window, &fix);
+            });
+            if result.is_err() {
+                warn!("[GPS Listener] Event loop encerrado, parando thread de atualizações");
+                break;
+            }
+        }
+    });
+
+    Ok(LocationUpdates { vm, listener })
+}
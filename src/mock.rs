@@ -0,0 +1,124 @@
+//! Provedor de localização mock/replay para desenvolvimento e testes.
+//!
+//! Habilitado pela feature `mock-location`. Substitui o stream real do
+//! `LocationManager` por uma trilha de waypoints fixa, reaproveitando o
+//! mesmo caminho de atualização da UI (`apply_fix_to_window`) usado pelo
+//! [`crate::listener`], de forma que o código da `MainWindow` não precise
+//! saber se os fixes vêm do hardware ou de uma trilha gravada. Útil para
+//! desenvolver e testar em emuladores ou dispositivos sem vista de céu.
+//!
+//! Ativado em tempo de execução pela variável de ambiente
+//! `GPS_MOCK_TRACK=1` (além da feature precisar estar habilitada em
+//! tempo de compilação), para não exigir um binário separado por cenário
+//! de teste.
+
+use crate::gps::GpsFix;
+use log::info;
+use std::time::Duration;
+
+/// Trilha de waypoints de exemplo (Av. Paulista, São Paulo), a única
+/// reproduzida pelo replay mock; não há carregamento de trilha de arquivo.
+const DEFAULT_TRACK: &[GpsFix] = &[
+    GpsFix {
+        latitude: -23.561_414,
+        longitude: -46.655_881,
+        altitude: Some(760.0),
+        speed: Some(0.0),
+        bearing: Some(0.0),
+        accuracy: Some(5.0),
+        timestamp_ms: 0,
+    },
+    GpsFix {
+        latitude: -23.562_899,
+        longitude: -46.654_312,
+        altitude: Some(762.0),
+        speed: Some(3.2),
+        bearing: Some(135.0),
+        accuracy: Some(5.0),
+        timestamp_ms: 0,
+    },
+    GpsFix {
+        latitude: -23.564_511,
+        longitude: -46.652_611,
+        altitude: Some(765.0),
+        speed: Some(3.5),
+        bearing: Some(140.0),
+        accuracy: Some(5.0),
+        timestamp_ms: 0,
+    },
+];
+
+/// Variável de ambiente que liga o replay mock em tempo de execução.
+const MOCK_TRACK_ENV_VAR: &str = "GPS_MOCK_TRACK";
+
+/// Indica se o replay mock deve ser usado no lugar do `LocationManager`
+/// real, segundo a variável de ambiente [`MOCK_TRACK_ENV_VAR`].
+pub fn is_enabled() -> bool {
+    std::env::var(MOCK_TRACK_ENV_VAR).is_ok_and(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+}
+
+/// Interpola linearmente entre dois fixes, usado para suavizar a
+/// transição entre waypoints em vez de "pular" de um para o outro.
+fn interpolate(from: &GpsFix, to: &GpsFix, t: f64) -> GpsFix {
+    let lerp = |a: f64, b: f64| a + (b - a) * t;
+    let lerp_opt = |a: Option<f64>, b: Option<f64>| match (a, b) {
+        (Some(a), Some(b)) => Some(lerp(a, b)),
+        _ => None,
+    };
+
+    GpsFix {
+        latitude: lerp(from.latitude, to.latitude),
+        longitude: lerp(from.longitude, to.longitude),
+        altitude: lerp_opt(from.altitude, to.altitude),
+        speed: lerp_opt(from.speed, to.speed),
+        bearing: lerp_opt(from.bearing, to.bearing),
+        accuracy: from.accuracy,
+        timestamp_ms: from.timestamp_ms,
+    }
+}
+
+/// Inicia a reprodução da trilha mock, atualizando `window_weak` a cada
+/// `interval_ms`. Para cada par de waypoints emite `interpolate_steps`
+/// fixes (no mínimo 1): o waypoint de origem seguido de
+/// `interpolate_steps - 1` fixes interpolados, para simular movimento
+/// contínuo em vez de saltos discretos. O último waypoint da trilha não é
+/// emitido; após o último par a reprodução recomeça do primeiro.
+pub fn start_mock_updates(
+    window_weak: slint::Weak<crate::MainWindow>,
+    interval_ms: u64,
+    interpolate_steps: u32,
+) {
+    std::thread::spawn(move || {
+        info!(
+            "[GPS Mock] Iniciando replay de {} waypoints (interval={}ms, steps={})",
+            DEFAULT_TRACK.len(),
+            interval_ms,
+            interpolate_steps
+        );
+
+        loop {
+            for pair in DEFAULT_TRACK.windows(2) {
+                let (from, to) = (&pair[0], &pair[1]);
+                let steps = interpolate_steps.max(1);
+
+                for step in 0..steps {
+                    let t = step as f64 / steps as f64;
+                    let fix = interpolate(from, to, t);
+
+                    // `Weak::upgrade` só é válido na thread do event loop;
+                    // agenda a atualização em vez de chamá-la diretamente
+                    // deste worker de replay.
+                    let result = window_weak.upgrade_in_event_loop(move |window| {
+                        crate::apply_fix_to_window(&window, &fix);
+                    });
+                    if result.is_err() {
+                        info!("[GPS Mock] Event loop encerrado, encerrando replay");
+                        return;
+                    }
+
+                    std::thread::sleep(Duration::from_millis(interval_ms));
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,435 @@
+//! Captura e parsing de sentenças NMEA via `OnNmeaMessageListener`.
+//!
+//! O `Location` do Android já entrega um fix processado, mas o fluxo
+//! NMEA bruto (o mesmo que a HAL expõe via `reportNmea`) carrega campos
+//! que o `Location` não expõe, como HDOP/VDOP, qualidade do fix e
+//! satélites usados diretamente da sentença GGA. Este módulo registra um
+//! `OnNmeaMessageListener`, captura cada sentença `$GPGGA`/`$GPGSA`/
+//! `$GPRMC` (aceitando qualquer prefixo de talker, ex. `$GN...`) e faz o
+//! parsing para structs tipadas.
+
+use crate::gps::{attach_env, location_manager};
+use crossbeam::channel::{unbounded, Receiver, Sender};
+use jni::objects::{GlobalRef, JObject, JValue};
+use jni::{JNIEnv, JavaVM, NativeMethod};
+use log::{error, info, warn};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::os::raw::c_void;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// Qualidade do fix reportada pelo campo 6 de uma sentença GGA.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FixQuality {
+    Invalid,
+    Gps,
+    DGps,
+    PpsFix,
+    RealTimeKinematic,
+    FloatRtk,
+    Estimated,
+    ManualInput,
+    Simulation,
+    Unknown(u8),
+}
+
+impl FixQuality {
+    fn from_code(code: u8) -> Self {
+        match code {
+            0 => FixQuality::Invalid,
+            1 => FixQuality::Gps,
+            2 => FixQuality::DGps,
+            3 => FixQuality::PpsFix,
+            4 => FixQuality::RealTimeKinematic,
+            5 => FixQuality::FloatRtk,
+            6 => FixQuality::Estimated,
+            7 => FixQuality::ManualInput,
+            8 => FixQuality::Simulation,
+            other => FixQuality::Unknown(other),
+        }
+    }
+}
+
+/// `$--GGA`: fix quality, HDOP, satélites usados e altitude.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GgaSentence {
+    pub fix_quality: FixQuality,
+    pub satellites_used: u8,
+    pub hdop: Option<f64>,
+    pub altitude_m: Option<f64>,
+}
+
+/// Modo de fix reportado pelo campo 2 de uma sentença GSA.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FixMode {
+    NoFix,
+    Fix2D,
+    Fix3D,
+    Unknown(u8),
+}
+
+impl FixMode {
+    fn from_code(code: u8) -> Self {
+        match code {
+            1 => FixMode::NoFix,
+            2 => FixMode::Fix2D,
+            3 => FixMode::Fix3D,
+            other => FixMode::Unknown(other),
+        }
+    }
+}
+
+/// `$--GSA`: modo de fix e PDOP/HDOP/VDOP.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GsaSentence {
+    pub fix_mode: FixMode,
+    pub pdop: Option<f64>,
+    pub hdop: Option<f64>,
+    pub vdop: Option<f64>,
+}
+
+/// `$--RMC`: velocidade, curso sobre o solo e data.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RmcSentence {
+    pub speed_knots: Option<f64>,
+    pub course_deg: Option<f64>,
+    /// Data no formato `ddmmyy`, como transmitida pelo receptor.
+    pub date_ddmmyy: Option<String>,
+}
+
+/// Uma sentença NMEA reconhecida e validada.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NmeaSentence {
+    Gga(GgaSentence),
+    Gsa(GsaSentence),
+    Rmc(RmcSentence),
+}
+
+/// Extrai o corpo (`$...`) e o checksum (`*HH`) de uma linha NMEA e
+/// confirma que o XOR dos bytes entre `$` e `*` bate com o checksum
+/// informado.
+fn verify_checksum(sentence: &str) -> Option<&str> {
+    let body = sentence.strip_prefix('$')?;
+    let (body, checksum_str) = body.split_once('*')?;
+    let expected = u8::from_str_radix(checksum_str.trim(), 16).ok()?;
+
+    let computed = body.bytes().fold(0u8, |acc, b| acc ^ b);
+    if computed != expected {
+        return None;
+    }
+
+    Some(body)
+}
+
+fn parse_field<T: std::str::FromStr>(field: Option<&str>) -> Option<T> {
+    field.filter(|s| !s.is_empty()).and_then(|s| s.parse().ok())
+}
+
+fn parse_gga(fields: &[&str]) -> Option<GgaSentence> {
+    Some(GgaSentence {
+        fix_quality: FixQuality::from_code(parse_field::<u8>(fields.get(5).copied())?),
+        satellites_used: parse_field(fields.get(6).copied()).unwrap_or(0),
+        hdop: parse_field(fields.get(7).copied()),
+        altitude_m: parse_field(fields.get(8).copied()),
+    })
+}
+
+fn parse_gsa(fields: &[&str]) -> Option<GsaSentence> {
+    Some(GsaSentence {
+        fix_mode: FixMode::from_code(parse_field::<u8>(fields.get(1).copied())?),
+        pdop: parse_field(fields.get(14).copied()),
+        hdop: parse_field(fields.get(15).copied()),
+        vdop: parse_field(fields.get(16).copied()),
+    })
+}
+
+fn parse_rmc(fields: &[&str]) -> Option<RmcSentence> {
+    Some(RmcSentence {
+        speed_knots: parse_field(fields.get(6).copied()),
+        course_deg: parse_field(fields.get(7).copied()),
+        date_ddmmyy: fields.get(8).filter(|s| !s.is_empty()).map(|s| s.to_string()),
+    })
+}
+
+/// Faz o parsing de uma linha NMEA crua, validando o checksum e
+/// reconhecendo GGA/GSA/RMC independentemente do talker (`GP`, `GN`,
+/// `GL`, ...). Sentenças malformadas ou de tipo não suportado retornam
+/// `None`; `subscribe_nmea` as descarta registrando um aviso no log.
+pub fn parse_nmea(raw: &str) -> Option<NmeaSentence> {
+    let body = verify_checksum(raw.trim())?;
+    let mut fields = body.split(',');
+    let sentence_id = fields.next()?;
+    let fields: Vec<&str> = fields.collect();
+
+    // `get` (em vez de indexação direta) evita um panic por
+    // "byte index is not a char boundary" quando `sentence_id` contém
+    // bytes não-ASCII: `verify_checksum` só garante que o XOR dos bytes
+    // bate, não que o corpo seja ASCII.
+    let kind = sentence_id.get(2..5)?;
+
+    match kind {
+        "GGA" => parse_gga(&fields).map(NmeaSentence::Gga),
+        "GSA" => parse_gsa(&fields).map(NmeaSentence::Gsa),
+        "RMC" => parse_rmc(&fields).map(NmeaSentence::Rmc),
+        _ => None,
+    }
+}
+
+/// Canal usado pelo callback nativo para entregar linhas NMEA cruas à
+/// thread consumidora. Só suporta um listener ativo por vez (ver
+/// checagem em `subscribe_nmea`), já que o método nativo registrado via
+/// `RegisterNatives` não carrega um identificador de instância.
+static RAW_SENDER: Mutex<Option<Sender<String>>> = Mutex::new(None);
+
+/// Alça do subsistema de NMEA. Mantém a referência global do listener
+/// Java viva e permite encerrar a captura.
+pub struct NmeaUpdates {
+    vm: JavaVM,
+    listener: GlobalRef,
+}
+
+impl NmeaUpdates {
+    pub fn stop(self) -> Result<(), Box<dyn std::error::Error>> {
+        let mut env = self.vm.attach_current_thread()?;
+        let (_, context) = attach_env()?;
+        let location_manager = location_manager(&mut env, &context)?;
+        env.call_method(
+            &location_manager,
+            "removeNmeaListener",
+            "(Landroid/location/OnNmeaMessageListener;)V",
+            &[JValue::Object(self.listener.as_obj())],
+        )?;
+        *RAW_SENDER.lock().unwrap() = None;
+        Ok(())
+    }
+}
+
+fn register_natives(env: &mut JNIEnv) -> Result<(), Box<dyn std::error::Error>> {
+    let class = env.find_class("com/gpsservice/RustNmeaListener")?;
+    let method = NativeMethod {
+        name: "nativeOnNmeaMessage".into(),
+        sig: "(Ljava/lang/String;J)V".into(),
+        fn_ptr: native_on_nmea_message as *mut c_void,
+    };
+    env.register_native_methods(&class, &[method])?;
+    Ok(())
+}
+
+/// Callback nativo invocado a cada `onNmeaMessage`. Apenas copia a
+/// string e empurra para o canal; todo o parsing acontece na thread
+/// consumidora, fora do contexto da JVM.
+extern "system" fn native_on_nmea_message<'local>(
+    mut env: JNIEnv<'local>,
+    _listener: JObject<'local>,
+    message: JObject<'local>,
+    _timestamp: i64,
+) {
+    let result: Result<(), Box<dyn std::error::Error>> = (|| {
+        let message = jni::objects::JString::from(message);
+        let raw: String = env.get_string(&message)?.into();
+
+        if let Some(sender) = RAW_SENDER.lock().unwrap().as_ref() {
+            sender.send(raw)?;
+        }
+        Ok(())
+    })();
+
+    if let Err(e) = result {
+        error!("[NMEA] Falha ao processar onNmeaMessage: {}", e);
+    }
+}
+
+/// Registra o `OnNmeaMessageListener` e chama `callback` para cada
+/// sentença GGA/GSA/RMC reconhecida. Sentenças malformadas (checksum
+/// inválido) ou de tipo não suportado são descartadas.
+///
+/// Quando `log_path` é informado, cada linha NMEA bruta (mesmo as
+/// descartadas pelo parser) também é anexada a esse arquivo, para
+/// diagnóstico posterior.
+///
+/// `callback` roda na thread consumidora deste módulo, não na thread do
+/// event loop do Slint — se precisar atualizar a `MainWindow`, use
+/// `Weak::upgrade_in_event_loop` dentro do próprio `callback`, como faz
+/// `crate::listener`.
+pub fn subscribe_nmea(
+    callback: impl Fn(NmeaSentence) + Send + 'static,
+    log_path: Option<PathBuf>,
+) -> Result<NmeaUpdates, Box<dyn std::error::Error>> {
+    if RAW_SENDER.lock().unwrap().is_some() {
+        return Err("Captura de NMEA já registrada; chame `stop()` antes de registrar outra".into());
+    }
+
+    let (vm, context) = attach_env()?;
+    let mut env = vm.attach_current_thread()?;
+
+    register_natives(&mut env)?;
+
+    let location_manager = location_manager(&mut env, &context)?;
+
+    let listener_class = env.find_class("com/gpsservice/RustNmeaListener")?;
+    let listener_local = env.new_object(&listener_class, "()V", &[])?;
+    let listener: GlobalRef = env.new_global_ref(listener_local)?;
+
+    // `addNmeaListener(OnNmeaMessageListener)` é a sobrecarga
+    // descontinuada que cria um `Handler` na thread chamadora, exigindo
+    // um Looper preparado — o que não é o caso desta thread de trabalho
+    // dedicada ao GPS. Usamos a sobrecarga com `Executor` (API 30+),
+    // passando `Context.getMainExecutor()`, para entregar as sentenças
+    // sempre na main thread.
+    let main_executor = env
+        .call_method(&context, "getMainExecutor", "()Ljava/util/concurrent/Executor;", &[])?
+        .l()?;
+
+    let registered = env
+        .call_method(
+            &location_manager,
+            "addNmeaListener",
+            "(Ljava/util/concurrent/Executor;Landroid/location/OnNmeaMessageListener;)Z",
+            &[JValue::Object(&main_executor), JValue::Object(listener.as_obj())],
+        )?
+        .z()?;
+
+    if !registered {
+        return Err("Não foi possível registrar o OnNmeaMessageListener".into());
+    }
+
+    let (sender, receiver): (Sender<String>, Receiver<String>) = unbounded();
+    *RAW_SENDER.lock().unwrap() = Some(sender);
+
+    std::thread::spawn(move || {
+        info!("[NMEA] Aguardando sentenças NMEA...");
+        for raw in receiver.iter() {
+            if let Some(path) = &log_path {
+                if let Err(e) = log_raw_sentence(path, &raw) {
+                    warn!("[NMEA] Falha ao gravar log de diagnóstico: {}", e);
+                }
+            }
+
+            match parse_nmea(&raw) {
+                Some(sentence) => callback(sentence),
+                None => warn!("[NMEA] Sentença malformada ou não suportada: {}", raw),
+            }
+        }
+    });
+
+    Ok(NmeaUpdates { vm, listener })
+}
+
+fn log_raw_sentence(path: &std::path::Path, raw: &str) -> std::io::Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", raw)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verify_checksum_accepts_correct_checksum() {
+        // XOR de "GPGGA,123519,..." bate com *47 (sentença de exemplo
+        // de referência do formato NMEA 0183).
+        let body = verify_checksum("$GPGGA,123519,4807.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,*47");
+        assert_eq!(body, Some("GPGGA,123519,4807.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,"));
+    }
+
+    #[test]
+    fn verify_checksum_rejects_wrong_checksum() {
+        assert_eq!(verify_checksum("$GPGGA,123519*00"), None);
+    }
+
+    #[test]
+    fn verify_checksum_rejects_missing_delimiters() {
+        assert_eq!(verify_checksum("GPGGA,123519*47"), None); // sem '$'
+        assert_eq!(verify_checksum("$GPGGA,123519"), None); // sem '*'
+    }
+
+    #[test]
+    fn parse_nmea_rejects_short_sentence_id() {
+        // Checksum de "AB" é 'A' ^ 'B' = 0x03.
+        assert_eq!(parse_nmea("$AB*03"), None);
+    }
+
+    #[test]
+    fn parse_nmea_rejects_non_ascii_sentence_id_without_panicking() {
+        // "GΩGA" tem 5 bytes mas o 'Ω' (2 bytes) não cai em um limite de
+        // char válido para o intervalo [2..5]; `parse_nmea` deve
+        // descartar a sentença em vez de entrar em pânico.
+        let body = "G\u{3A9}GA,1,2,3";
+        let checksum = body.bytes().fold(0u8, |acc, b| acc ^ b);
+        let raw = format!("${}*{:02X}", body, checksum);
+        assert_eq!(parse_nmea(&raw), None);
+    }
+
+    #[test]
+    fn parse_nmea_rejects_unsupported_sentence_kind() {
+        let body = "GPXXX,1,2,3";
+        let checksum = body.bytes().fold(0u8, |acc, b| acc ^ b);
+        let raw = format!("${}*{:02X}", body, checksum);
+        assert_eq!(parse_nmea(&raw), None);
+    }
+
+    #[test]
+    fn parse_gga_reads_fix_quality_satellites_hdop_altitude() {
+        let fields = ["123519", "", "", "", "", "1", "08", "0.9", "545.4", "M"];
+        let gga = parse_gga(&fields).unwrap();
+        assert_eq!(gga.fix_quality, FixQuality::Gps);
+        assert_eq!(gga.satellites_used, 8);
+        assert_eq!(gga.hdop, Some(0.9));
+        assert_eq!(gga.altitude_m, Some(545.4));
+    }
+
+    #[test]
+    fn parse_gga_missing_fix_quality_is_malformed() {
+        let fields = ["123519", "", "", "", "", "", "08", "0.9", "545.4", "M"];
+        assert_eq!(parse_gga(&fields), None);
+    }
+
+    #[test]
+    fn parse_gga_missing_optional_fields_defaults_sensibly() {
+        let fields = ["123519", "", "", "", "", "1"];
+        let gga = parse_gga(&fields).unwrap();
+        assert_eq!(gga.fix_quality, FixQuality::Gps);
+        assert_eq!(gga.satellites_used, 0);
+        assert_eq!(gga.hdop, None);
+        assert_eq!(gga.altitude_m, None);
+    }
+
+    #[test]
+    fn parse_gsa_reads_fix_mode_and_dops() {
+        let mut fields = vec![""; 17];
+        fields[1] = "3";
+        fields[14] = "1.5";
+        fields[15] = "0.9";
+        fields[16] = "1.2";
+        let gsa = parse_gsa(&fields).unwrap();
+        assert_eq!(gsa.fix_mode, FixMode::Fix3D);
+        assert_eq!(gsa.pdop, Some(1.5));
+        assert_eq!(gsa.hdop, Some(0.9));
+        assert_eq!(gsa.vdop, Some(1.2));
+    }
+
+    #[test]
+    fn parse_gsa_missing_fix_mode_is_malformed() {
+        let fields = vec![""; 17];
+        assert_eq!(parse_gsa(&fields), None);
+    }
+
+    #[test]
+    fn parse_rmc_reads_speed_course_and_date() {
+        let fields = ["123519", "A", "", "", "", "", "022.4", "084.4", "230394"];
+        let rmc = parse_rmc(&fields).unwrap();
+        assert_eq!(rmc.speed_knots, Some(22.4));
+        assert_eq!(rmc.course_deg, Some(84.4));
+        assert_eq!(rmc.date_ddmmyy, Some("230394".to_string()));
+    }
+
+    #[test]
+    fn parse_rmc_missing_optional_fields_is_none() {
+        let fields = ["123519", "V"];
+        let rmc = parse_rmc(&fields).unwrap();
+        assert_eq!(rmc.speed_knots, None);
+        assert_eq!(rmc.course_deg, None);
+        assert_eq!(rmc.date_ddmmyy, None);
+    }
+}
@@ -0,0 +1,78 @@
+//! Fluxo de solicitação de permissão de localização em tempo de execução.
+//!
+//! Em Android 6+ (API 23+), `ACCESS_FINE_LOCATION` não pode mais ser
+//! simplesmente verificada e negada — é preciso disparar o diálogo padrão
+//! do sistema via `requestPermissions` e aguardar a decisão do usuário.
+//! Este módulo troca a falha imediata de `start_location_updates`/
+//! `fusion::get_best_fix` por esse fluxo.
+
+use crate::gps::{attach_env, has_any_location_permission};
+use jni::objects::{JObject, JValue};
+use log::{info, warn};
+use std::time::Duration;
+
+/// Código de requisição arbitrário repassado a `requestPermissions`/
+/// `onRequestPermissionsResult`. Só precisa ser único dentro da Activity.
+const LOCATION_PERMISSION_REQUEST_CODE: i32 = 4242;
+
+/// Tempo máximo de espera pela decisão do usuário antes de desistir.
+const MAX_WAIT: Duration = Duration::from_secs(30);
+
+/// Garante que ao menos uma entre `ACCESS_FINE_LOCATION`/
+/// `ACCESS_COARSE_LOCATION` esteja concedida, disparando o diálogo de
+/// permissão do Android quando necessário.
+///
+/// `onRequestPermissionsResult` é entregue à Activity, e este projeto não
+/// tem uma Activity própria para interceptar o callback via
+/// `RegisterNatives`. Em vez disso, após chamar `requestPermissions`,
+/// fazemos polling de `checkSelfPermission` com backoff exponencial —
+/// suficiente para cobrir o tempo que o usuário leva para responder ao
+/// diálogo.
+pub fn request_location_permission(
+    on_waiting: impl Fn(),
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (vm, context) = attach_env()?;
+    let mut env = vm.attach_current_thread()?;
+
+    if has_any_location_permission(&mut env, &context)? {
+        return Ok(());
+    }
+
+    info!("[GPS Permission] Solicitando ACCESS_FINE_LOCATION/ACCESS_COARSE_LOCATION ao usuário");
+    on_waiting();
+
+    let string_class = env.find_class("java/lang/String")?;
+    let permissions = env.new_object_array(2, &string_class, JObject::null())?;
+    let fine = env.new_string("android.permission.ACCESS_FINE_LOCATION")?;
+    let coarse = env.new_string("android.permission.ACCESS_COARSE_LOCATION")?;
+    env.set_object_array_element(&permissions, 0, fine)?;
+    env.set_object_array_element(&permissions, 1, coarse)?;
+
+    env.call_method(
+        &context,
+        "requestPermissions",
+        "([Ljava/lang/String;I)V",
+        &[
+            JValue::Object(&JObject::from(permissions)),
+            JValue::Int(LOCATION_PERMISSION_REQUEST_CODE),
+        ],
+    )?;
+
+    let mut waited = Duration::ZERO;
+    let mut backoff = Duration::from_millis(250);
+
+    while waited < MAX_WAIT {
+        std::thread::sleep(backoff);
+        waited += backoff;
+
+        if has_any_location_permission(&mut env, &context)? {
+            info!("[GPS Permission] Permissão concedida após {:?}", waited);
+            return Ok(());
+        }
+
+        backoff = (backoff * 2).min(Duration::from_secs(2));
+    }
+
+    warn!("[GPS Permission] Usuário não concedeu a permissão dentro do prazo");
+    Err("Permissão de localização não concedida pelo usuário".into())
+}
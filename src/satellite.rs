@@ -0,0 +1,219 @@
+//! Status de satélites/GNSS via `GnssStatus.Callback`.
+//!
+//! O `Location` entregue por `LocationListener` só traz o fix já
+//! calculado; ele não diz quantos satélites estão em vista nem a
+//! qualidade do sinal de cada um. Este módulo registra um
+//! `GnssStatus.Callback` (a sucessora moderna da antiga `GpsSvInfo` da
+//! HAL) para reportar essa informação separadamente, permitindo
+//! distinguir "sem fix ainda, 0 satélites" de "adquirindo, 8 em vista".
+
+use crate::gps::{attach_env, location_manager};
+use crossbeam::channel::{unbounded, Receiver, Sender};
+use jni::objects::{GlobalRef, JObject, JValue};
+use jni::{JNIEnv, JavaVM, NativeMethod};
+use log::{error, info, warn};
+use std::os::raw::c_void;
+use std::sync::Mutex;
+
+use crate::MainWindow;
+
+/// Informação de um único satélite reportado pelo `GnssStatus`.
+#[derive(Debug, Clone, Copy)]
+pub struct SatelliteInfo {
+    /// Constante `GnssStatus.CONSTELLATION_*` (GPS, GLONASS, Galileo, ...).
+    pub constellation: i32,
+    pub svid: i32,
+    pub cn0_dbhz: f32,
+    pub elevation_deg: f32,
+    pub azimuth_deg: f32,
+    pub used_in_fix: bool,
+}
+
+/// Status agregado de todos os satélites visíveis no momento.
+#[derive(Debug, Clone)]
+pub struct SatelliteStatus {
+    pub satellites: Vec<SatelliteInfo>,
+}
+
+impl SatelliteStatus {
+    pub fn in_view(&self) -> usize {
+        self.satellites.len()
+    }
+
+    pub fn used_in_fix(&self) -> usize {
+        self.satellites.iter().filter(|s| s.used_in_fix).count()
+    }
+
+    /// C/N0 médio (dB-Hz) entre todos os satélites em vista, ou `None`
+    /// se nenhum satélite estiver visível.
+    pub fn average_cn0_dbhz(&self) -> Option<f32> {
+        if self.satellites.is_empty() {
+            return None;
+        }
+        let sum: f32 = self.satellites.iter().map(|s| s.cn0_dbhz).sum();
+        Some(sum / self.satellites.len() as f32)
+    }
+}
+
+/// Canal usado pelo callback nativo para entregar atualizações de status
+/// de satélites à thread consumidora. Só suporta um callback ativo por
+/// vez (ver checagem em `start_satellite_updates`), já que o método
+/// nativo registrado via `RegisterNatives` não carrega um identificador
+/// de instância.
+static STATUS_SENDER: Mutex<Option<Sender<SatelliteStatus>>> = Mutex::new(None);
+
+/// Alça do subsistema de status de satélites. Mantém a referência global
+/// do callback Java viva e permite encerrar o registro chamando
+/// `unregisterGnssStatusCallback`.
+pub struct GnssStatusUpdates {
+    vm: JavaVM,
+    callback: GlobalRef,
+}
+
+impl GnssStatusUpdates {
+    pub fn stop(self) -> Result<(), Box<dyn std::error::Error>> {
+        let mut env = self.vm.attach_current_thread()?;
+        let (_, context) = attach_env()?;
+        let location_manager = location_manager(&mut env, &context)?;
+        env.call_method(
+            &location_manager,
+            "unregisterGnssStatusCallback",
+            "(Landroid/location/GnssStatus$Callback;)V",
+            &[JValue::Object(self.callback.as_obj())],
+        )?;
+        *STATUS_SENDER.lock().unwrap() = None;
+        Ok(())
+    }
+}
+
+fn register_natives(env: &mut JNIEnv) -> Result<(), Box<dyn std::error::Error>> {
+    let class = env.find_class("com/gpsservice/RustGnssStatusCallback")?;
+    let method = NativeMethod {
+        name: "nativeOnSatelliteStatusChanged".into(),
+        sig: "(Landroid/location/GnssStatus;)V".into(),
+        fn_ptr: native_on_satellite_status_changed as *mut c_void,
+    };
+    env.register_native_methods(&class, &[method])?;
+    Ok(())
+}
+
+/// Callback nativo invocado a cada `onSatelliteStatusChanged`. Lê todos os
+/// satélites reportados pelo `GnssStatus` e empurra o status agregado
+/// para o canal.
+extern "system" fn native_on_satellite_status_changed<'local>(
+    mut env: JNIEnv<'local>,
+    _callback: JObject<'local>,
+    status: JObject<'local>,
+) {
+    let result: Result<(), Box<dyn std::error::Error>> = (|| {
+        let count = env.call_method(&status, "getSatelliteCount", "()I", &[])?.i()?;
+
+        let mut satellites = Vec::with_capacity(count as usize);
+        for i in 0..count {
+            let constellation = env
+                .call_method(&status, "getConstellationType", "(I)I", &[JValue::Int(i)])?
+                .i()?;
+            let svid = env.call_method(&status, "getSvid", "(I)I", &[JValue::Int(i)])?.i()?;
+            let cn0_dbhz = env
+                .call_method(&status, "getCn0DbHz", "(I)F", &[JValue::Int(i)])?
+                .f()?;
+            let elevation_deg = env
+                .call_method(&status, "getElevationDegrees", "(I)F", &[JValue::Int(i)])?
+                .f()?;
+            let azimuth_deg = env
+                .call_method(&status, "getAzimuthDegrees", "(I)F", &[JValue::Int(i)])?
+                .f()?;
+            let used_in_fix = env
+                .call_method(&status, "usedInFix", "(I)Z", &[JValue::Int(i)])?
+                .z()?;
+
+            satellites.push(SatelliteInfo {
+                constellation,
+                svid,
+                cn0_dbhz,
+                elevation_deg,
+                azimuth_deg,
+                used_in_fix,
+            });
+        }
+
+        if let Some(sender) = STATUS_SENDER.lock().unwrap().as_ref() {
+            sender.send(SatelliteStatus { satellites })?;
+        }
+        Ok(())
+    })();
+
+    if let Err(e) = result {
+        error!("[GNSS Status] Falha ao processar onSatelliteStatusChanged: {}", e);
+    }
+}
+
+/// Registra o `GnssStatus.Callback` e atualiza `window_weak` com a
+/// contagem de satélites em vista/usados e o C/N0 médio a cada mudança.
+pub fn start_satellite_updates(
+    window_weak: slint::Weak<MainWindow>,
+) -> Result<GnssStatusUpdates, Box<dyn std::error::Error>> {
+    if STATUS_SENDER.lock().unwrap().is_some() {
+        return Err("Status de satélites já registrado; chame `stop()` antes de registrar outro".into());
+    }
+
+    let (vm, context) = attach_env()?;
+    let mut env = vm.attach_current_thread()?;
+
+    register_natives(&mut env)?;
+
+    let location_manager = location_manager(&mut env, &context)?;
+
+    let callback_class = env.find_class("com/gpsservice/RustGnssStatusCallback")?;
+    let callback_local = env.new_object(&callback_class, "()V", &[])?;
+    let callback: GlobalRef = env.new_global_ref(callback_local)?;
+
+    // `registerGnssStatusCallback(Callback)` é a sobrecarga descontinuada
+    // que cria um `Handler` na thread chamadora, exigindo que ela já
+    // tenha um Looper preparado — o que não é o caso desta thread de
+    // trabalho dedicada ao GPS. Usamos a sobrecarga com `Executor`
+    // (API 30+), passando `Context.getMainExecutor()`, para que o
+    // callback seja entregue na main thread independentemente de quem
+    // chamou o registro.
+    let main_executor = env
+        .call_method(&context, "getMainExecutor", "()Ljava/util/concurrent/Executor;", &[])?
+        .l()?;
+
+    let registered = env
+        .call_method(
+            &location_manager,
+            "registerGnssStatusCallback",
+            "(Ljava/util/concurrent/Executor;Landroid/location/GnssStatus$Callback;)Z",
+            &[JValue::Object(&main_executor), JValue::Object(callback.as_obj())],
+        )?
+        .z()?;
+
+    if !registered {
+        return Err("Não foi possível registrar o GnssStatus.Callback".into());
+    }
+
+    let (sender, receiver): (Sender<SatelliteStatus>, Receiver<SatelliteStatus>) = unbounded();
+    *STATUS_SENDER.lock().unwrap() = Some(sender);
+
+    std::thread::spawn(move || {
+        info!("[GNSS Status] Aguardando atualizações de satélites...");
+        for status in receiver.iter() {
+            info!(
+                "[GNSS Status] {} em vista, {} usados no fix",
+                status.in_view(),
+                status.used_in_fix()
+            );
+            // `Weak::upgrade` só é válido na thread do event loop; agenda
+            // a atualização em vez de chamá-la diretamente deste worker.
+            let result = window_weak.upgrade_in_event_loop(move |window| {
+                crate::apply_satellite_status_to_window(&window, &status);
+            });
+            if result.is_err() {
+                warn!("[GNSS Status] Event loop encerrado, parando thread de atualizações");
+                break;
+            }
+        }
+    });
+
+    Ok(GnssStatusUpdates { vm, callback })
+}